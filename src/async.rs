@@ -0,0 +1,382 @@
+//! Versões assíncronas das funções de download, construídas sobre o `Client`
+//! não bloqueante do reqwest e sobre `tokio::fs`.
+//!
+//! Disponível apenas com a feature `async`. As funções devem ser executadas dentro de
+//! um runtime tokio e retornam o mesmo [`DownloadError`] da API bloqueante.
+
+use std::io;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use futures::stream::{self, StreamExt};
+use futures::FutureExt;
+use reqwest::Client;
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+use crate::config::DownloadConfig;
+use crate::download::{check_status, panic_message, safe_relative_path};
+use crate::error::DownloadError;
+
+/// Realiza o download de um arquivo a partir de uma URL especificada e o salva em `dest`,
+/// de forma assíncrona.
+///
+/// O corpo da resposta é gravado no arquivo à medida que chega (via `bytes_stream()`),
+/// sem ser mantido inteiro em memória. As validações são as mesmas de
+/// [`crate::download::download_file_to`].
+///
+/// # Argumentos
+///
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest`.
+/// * `dest`: O diretório onde o arquivo será salvo; é criado se ainda não existir.
+///
+/// # Retorno
+///
+/// O número de bytes gravados no arquivo local, em caso de sucesso.
+///
+/// # Erros
+///
+/// Os mesmos de [`crate::download::download_file_to`]. O `Client` usado é o de
+/// [`DownloadConfig::build_async_client`] com a configuração padrão, de modo que um servidor
+/// que para de responder resulta em [`DownloadError::Timeout`] após 30 segundos.
+pub async fn download_file_async(
+    url: &str,
+    filename: &str,
+    dest: &Path,
+) -> Result<u64, DownloadError> {
+    let client = DownloadConfig::default().build_async_client()?;
+    download_with(&client, url, filename, dest).await
+}
+
+/// Realiza o download de um arquivo para `dest` de forma assíncrona, usando um `Client`
+/// já construído (ex: por [`DownloadConfig::build_async_client`]).
+///
+/// # Argumentos
+///
+/// * `client`: O `Client` assíncrono usado para enviar a requisição.
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest`.
+/// * `dest`: O diretório onde o arquivo será salvo; é criado se ainda não existir.
+///
+/// # Retorno
+///
+/// O número de bytes gravados no arquivo local, em caso de sucesso.
+///
+/// # Erros
+///
+/// Os mesmos de [`crate::download::download_file_to`].
+pub async fn download_file_async_with(
+    client: &Client,
+    url: &str,
+    filename: &str,
+    dest: &Path,
+) -> Result<u64, DownloadError> {
+    download_with(client, url, filename, dest).await
+}
+
+/// Realiza o download de vários arquivos de forma assíncrona, com no máximo
+/// `max_concurrency` downloads em andamento ao mesmo tempo, e os salva no diretório
+/// local "downloads".
+///
+/// Equivale a chamar [`download_all_async_with`] com o `Client` padrão de
+/// [`DownloadConfig::build_async_client`] e `Path::new("downloads")` como destino.
+///
+/// # Argumentos
+///
+/// * `url`: A URL base de onde os arquivos serão baixados.
+/// * `filenames`: Os nomes dos arquivos a serem baixados.
+/// * `max_concurrency`: O número máximo de downloads simultâneos. O valor `0` é tratado como `1`.
+///
+/// # Retorno
+///
+/// O mesmo de [`download_all_async_with`].
+pub async fn download_all_async(
+    url: &str,
+    filenames: &[&str],
+    max_concurrency: usize,
+) -> Vec<(String, Result<u64, DownloadError>)> {
+    // Um único `Client` para todos os downloads, para que reaproveitem as conexões.
+    let client = DownloadConfig::default().build_async_client();
+    let download_dir = Path::new("downloads");
+    download_all(client.as_ref().ok(), url, filenames, download_dir, max_concurrency).await
+}
+
+/// Realiza o download de vários arquivos para `dest` de forma assíncrona, com no máximo
+/// `max_concurrency` downloads em andamento ao mesmo tempo e um `Client` já construído.
+///
+/// É o equivalente assíncrono de [`crate::download::download_all_with`]: todos os
+/// downloads compartilham `client` e, portanto, o pool de conexões e os tempos limite.
+///
+/// # Argumentos
+///
+/// * `client`: O `Client` assíncrono usado por todos os downloads.
+/// * `url`: A URL base de onde os arquivos serão baixados.
+/// * `filenames`: Os nomes dos arquivos a serem baixados.
+/// * `dest`: O diretório onde os arquivos serão salvos.
+/// * `max_concurrency`: O número máximo de downloads simultâneos. O valor `0` é tratado como `1`.
+///
+/// # Retorno
+///
+/// Um `Vec` com um par `(filename, resultado)` para cada arquivo, na mesma ordem de `filenames`.
+/// Um pânico durante o download de um arquivo é capturado e reportado como
+/// [`DownloadError::Panicked`] apenas para aquele arquivo.
+pub async fn download_all_async_with(
+    client: &Client,
+    url: &str,
+    filenames: &[&str],
+    dest: &Path,
+    max_concurrency: usize,
+) -> Vec<(String, Result<u64, DownloadError>)> {
+    download_all(Some(client), url, filenames, dest, max_concurrency).await
+}
+
+/// Implementação comum de [`download_all_async`] e [`download_all_async_with`]. Sem
+/// `client`, cada download tenta criar o seu e reporta o próprio erro.
+async fn download_all(
+    client: Option<&Client>,
+    url: &str,
+    filenames: &[&str],
+    dest: &Path,
+    max_concurrency: usize,
+) -> Vec<(String, Result<u64, DownloadError>)> {
+    let downloads = filenames
+        .iter()
+        .enumerate()
+        .map(|(index, filename)| async move {
+            let download = async {
+                match client {
+                    Some(client) => download_with(client, url, filename, dest).await,
+                    None => download_file_async(url, filename, dest).await,
+                }
+            };
+
+            // Captura um eventual pânico para que ele afete apenas este arquivo.
+            let result = AssertUnwindSafe(download)
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|payload| {
+                    Err(DownloadError::Panicked {
+                        message: panic_message(payload.as_ref()),
+                    })
+                });
+            (index, result)
+        });
+
+    // `buffer_unordered` mantém no máximo `max_concurrency` downloads em andamento e
+    // entrega os resultados conforme terminam; o índice restaura a ordem de entrada.
+    let mut results: Vec<(usize, Result<u64, DownloadError>)> = stream::iter(downloads)
+        .buffer_unordered(max_concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by_key(|(index, _)| *index);
+
+    results
+        .into_iter()
+        .map(|(index, result)| (filenames[index].to_string(), result))
+        .collect()
+}
+
+/// Implementação comum de todas as funções deste módulo.
+async fn download_with(
+    client: &Client,
+    url: &str,
+    filename: &str,
+    dest: &Path,
+) -> Result<u64, DownloadError> {
+    // Valida o nome do arquivo antes de qualquer requisição, para nunca gravar fora de `dest`.
+    let relative_path = safe_relative_path(filename)?;
+
+    // Um arquivo regular (ou outro tipo de entrada) no lugar do diretório de destino
+    // é reportado explicitamente antes da requisição, como na versão bloqueante.
+    if let Ok(metadata) = fs::metadata(dest).await {
+        if !metadata.is_dir() {
+            return Err(DownloadError::NotADirectory {
+                path: dest.to_path_buf(),
+            });
+        }
+    }
+
+    // Constrói a URL completa do arquivo combinando a URL base e o nome do arquivo.
+    let file_url = format!("{}/{}", url, filename);
+
+    // Envia a requisição GET e verifica o status HTTP da resposta.
+    let response = client.get(&file_url).send().await?;
+    check_status(response.status(), filename)?;
+
+    // Cria o diretório de destino e os subdiretórios de nomes aninhados (ex: "todos" em "todos/1").
+    let local_file_path = dest.join(&relative_path);
+    if let Some(parent) = local_file_path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .map_err(DownloadError::CreateDir)?;
+    }
+
+    // Cria (ou sobrescreve, se já existir) o arquivo local onde o conteúdo será salvo.
+    let mut local_file = File::create(&local_file_path)
+        .await
+        .map_err(DownloadError::CreateFile)?;
+
+    // Grava cada bloco do corpo assim que ele chega.
+    let mut body = response.bytes_stream();
+    let mut bytes_copied: u64 = 0;
+    while let Some(chunk) = body.next().await {
+        // Erros ao ler o corpo equivalem a falhas de cópia na versão bloqueante.
+        let chunk = chunk.map_err(|e| {
+            if e.is_timeout() {
+                DownloadError::Timeout
+            } else {
+                DownloadError::Copy(io::Error::other(e))
+            }
+        })?;
+        local_file
+            .write_all(&chunk)
+            .await
+            .map_err(DownloadError::Copy)?;
+        bytes_copied += chunk.len() as u64;
+    }
+
+    // O `File` do tokio grava em segundo plano; `flush` garante que tudo foi escrito
+    // (e que eventuais erros de escrita sejam reportados) antes de retornar.
+    local_file.flush().await.map_err(DownloadError::Copy)?;
+
+    // Conteúdo copiado com sucesso. Imprime uma mensagem de sucesso.
+    println!(
+        "Download do arquivo '{}' para '{}' ({} bytes) concluído com sucesso!",
+        filename,
+        local_file_path.display(),
+        bytes_copied
+    );
+
+    Ok(bytes_copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::io::Write;
+    use std::thread;
+    use std::time::Duration;
+    use crate::download::tests::{respond, serve, serve_content};
+
+    /// Executa `future` em um runtime tokio de uma única thread.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// `Client` assíncrono com um tempo limite curto, para os testes de servidores que travam.
+    fn client_with_short_timeout() -> Client {
+        DownloadConfig {
+            timeout: Some(Duration::from_millis(300)),
+            ..Default::default()
+        }
+        .build_async_client()
+        .unwrap()
+    }
+
+    #[test]
+    fn download_file_async_writes_the_file() {
+        let url = serve_content(b"conteudo");
+        let dir = tempfile::tempdir().unwrap();
+
+        let bytes = block_on(download_file_async(&url, "todos/1", dir.path())).unwrap();
+
+        assert_eq!(bytes, 8);
+        assert_eq!(
+            std::fs::read(dir.path().join("todos/1")).unwrap(),
+            b"conteudo"
+        );
+    }
+
+    #[test]
+    fn download_file_async_reports_unsafe_path_and_not_found() {
+        let url = serve(|_, stream| respond(stream, "404 Not Found", &[], b""));
+        let dir = tempfile::tempdir().unwrap();
+
+        let result = block_on(download_file_async(&url, "../escape", dir.path()));
+        assert!(
+            matches!(result, Err(DownloadError::UnsafePath { .. })),
+            "{:?}",
+            result
+        );
+
+        let result = block_on(download_file_async(&url, "a.txt", dir.path()));
+        assert!(
+            matches!(&result, Err(DownloadError::NotFound { filename }) if filename == "a.txt"),
+            "{:?}",
+            result
+        );
+        assert!(!dir.path().join("a.txt").exists());
+    }
+
+    #[test]
+    fn download_file_async_with_times_out_when_server_never_responds() {
+        let url = serve(|_, _| thread::sleep(Duration::from_secs(10)));
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_short_timeout();
+
+        let result = block_on(download_file_async_with(&client, &url, "a.txt", dir.path()));
+
+        assert!(
+            matches!(result, Err(DownloadError::Timeout)),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn download_file_async_with_times_out_when_body_stalls() {
+        let url = serve(|_, stream| {
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n0123456789");
+            let _ = stream.flush();
+            thread::sleep(Duration::from_secs(10));
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_short_timeout();
+
+        let result = block_on(download_file_async_with(&client, &url, "a.txt", dir.path()));
+
+        assert!(
+            matches!(result, Err(DownloadError::Timeout)),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn download_all_async_with_keeps_input_order() {
+        let url = serve(|request, stream| {
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            respond(
+                stream,
+                "200 OK",
+                &[],
+                path.trim_start_matches('/').as_bytes(),
+            );
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_short_timeout();
+        let filenames = ["a.txt", "bb.txt", "ccc.txt", "../x"];
+
+        let results = block_on(download_all_async_with(
+            &client,
+            &url,
+            &filenames,
+            dir.path(),
+            0,
+        ));
+
+        let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, filenames);
+        for (filename, result) in &results[..3] {
+            assert_eq!(*result.as_ref().unwrap(), filename.len() as u64);
+        }
+        assert!(matches!(
+            results[3].1,
+            Err(DownloadError::UnsafePath { .. })
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+use reqwest::blocking::Client;
+use crate::error::DownloadError;
+
+/// Tempo limite usado pelo `Client` assíncrono quando `timeout` é `None`; é o mesmo
+/// padrão do `Client` bloqueante do reqwest.
+#[cfg(feature = "async")]
+const DEFAULT_ASYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Configuração do `Client` HTTP usado nos downloads.
+///
+/// Um mesmo `Client` pode (e deve) ser reaproveitado por vários downloads, para que
+/// eles compartilhem o pool de conexões. Veja [`crate::download::download_file_with`],
+/// [`crate::download::download_file_with_options`] e [`crate::download::download_all_with`].
+///
+/// # Exemplos
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use test_thread_download::config::DownloadConfig;
+///
+/// let config = DownloadConfig {
+///     connect_timeout: Some(Duration::from_secs(5)),
+///     timeout: Some(Duration::from_secs(60)),
+///     ..Default::default()
+/// };
+/// let client = config.build_client().expect("falha ao criar o Client");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DownloadConfig {
+    /// Tempo máximo para estabelecer a conexão com o servidor.
+    /// `None` mantém o padrão do reqwest (sem limite específico para a conexão).
+    pub connect_timeout: Option<Duration>,
+    /// Tempo máximo de espera pela resposta e por cada leitura do corpo. Um servidor que
+    /// para de enviar dados por mais que esse tempo interrompe o download.
+    /// `None` mantém o padrão do `Client` bloqueante do reqwest (30 segundos).
+    pub timeout: Option<Duration>,
+    /// Valor do cabeçalho `User-Agent` enviado em cada requisição.
+    /// `None` não envia o cabeçalho.
+    pub user_agent: Option<String>,
+}
+
+impl DownloadConfig {
+    /// Constrói um `Client` bloqueante com os tempos limite e o user-agent configurados.
+    ///
+    /// # Erros
+    ///
+    /// Retorna [`DownloadError::Request`] se o `Client` não puder ser criado
+    /// (ex: falha ao inicializar o backend TLS).
+    pub fn build_client(&self) -> Result<Client, DownloadError> {
+        let mut builder = Client::builder();
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent.clone());
+        }
+
+        builder.build().map_err(DownloadError::Request)
+    }
+
+    /// Constrói um `Client` assíncrono com os mesmos tempos limite e user-agent.
+    ///
+    /// Disponível apenas com a feature `async`. Diferente do bloqueante, o `Client`
+    /// assíncrono do reqwest não tem tempo limite padrão, e um servidor travado deixaria o
+    /// download pendente para sempre. Para manter o comportamento da versão bloqueante,
+    /// `timeout` é aplicado à espera pela resposta e a cada leitura do corpo
+    /// (`read_timeout`) e, quando `None`, vale 30 segundos.
+    ///
+    /// # Erros
+    ///
+    /// Retorna [`DownloadError::Request`] se o `Client` não puder ser criado
+    /// (ex: falha ao inicializar o backend TLS).
+    #[cfg(feature = "async")]
+    pub fn build_async_client(&self) -> Result<reqwest::Client, DownloadError> {
+        let mut builder = reqwest::Client::builder()
+            .read_timeout(self.timeout.unwrap_or(DEFAULT_ASYNC_TIMEOUT));
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent.clone());
+        }
+
+        builder.build().map_err(DownloadError::Request)
+    }
+}
@@ -1,11 +1,27 @@
-use std::fs::File;
-use std::path::Path;
-use reqwest::blocking::Response; // Importação explícita para clareza
+use std::any::Any;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use reqwest::blocking::{Client, Response}; // Importação explícita para clareza
+use reqwest::header::{HeaderMap, CONTENT_RANGE, RANGE};
 use reqwest::StatusCode; // Importado para usar reqwest::StatusCode::NOT_FOUND
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use crate::config::DownloadConfig;
+use crate::error::DownloadError;
+
+/// Tamanho do buffer usado para copiar o corpo da resposta para o arquivo local.
+const BUFFER_SIZE: usize = 64 * 1024;
 
 /// Realiza o download de um arquivo a partir de uma URL especificada e o salva
 /// no diretório local "downloads".
 ///
+/// Equivale a chamar [`download_file_to`] com `Path::new("downloads")` como destino.
 /// O diretório "downloads" será criado se ainda não existir.
 ///
 /// # Argumentos
@@ -14,170 +30,1940 @@ use reqwest::StatusCode; // Importado para usar reqwest::StatusCode::NOT_FOUND
 /// * `filename`: Uma string (`&str`) que representa o nome do arquivo a ser baixado.
 ///   Este nome também será usado para salvar o arquivo localmente no diretório "downloads".
 ///
-/// # Panics
+/// # Retorno
+///
+/// O número de bytes gravados no arquivo local, em caso de sucesso.
+///
+/// # Erros
+///
+/// Os mesmos de [`download_file_to`].
+///
+/// # Exemplos
+///
+/// ```no_run
+/// use test_thread_download::download::download_file;
+/// use test_thread_download::error::DownloadError;
+///
+/// // Exemplo com um arquivo que pode existir (usado para testes de API pública)
+/// // Note que "todos/1" será salvo como "downloads/todos/1", criando o subdiretório "todos".
+/// match download_file("https://jsonplaceholder.typicode.com", "todos/1") {
+///     Ok(bytes) => println!("{} bytes baixados", bytes),
+///     Err(DownloadError::NotFound { filename }) => println!("'{}' não existe", filename),
+///     Err(e) => println!("Falha no download: {}", e),
+/// }
+/// ```
+pub fn download_file(url: &str, filename: &str) -> Result<u64, DownloadError> {
+    download_file_to(url, filename, Path::new("downloads"))
+}
+
+/// Realiza o download de um arquivo a partir de uma URL especificada e o salva
+/// no diretório `dest_dir`.
+///
+/// O diretório `dest_dir` (e seus diretórios pais) será criado se ainda não existir.
+///
+/// # Argumentos
+///
+/// * `url`: Uma string (`&str`) que representa a URL base de onde o arquivo será baixado.
+/// * `filename`: Uma string (`&str`) que representa o nome do arquivo a ser baixado.
+///   Este nome também será usado para salvar o arquivo localmente em `dest_dir`.
+///   Nomes aninhados (ex: "todos/1") são permitidos e criam os subdiretórios
+///   intermediários, desde que permaneçam dentro de `dest_dir`.
+/// * `dest_dir`: O diretório onde o arquivo será salvo.
 ///
-/// Esta função entrará em pânico (`panic!`) em diversas situações:
+/// # Retorno
 ///
-/// * Se houver uma falha ao enviar a requisição GET inicial (ex: erro de DNS,
+/// O número de bytes gravados no arquivo local, em caso de sucesso.
+///
+/// # Erros
+///
+/// Esta função retorna um [`DownloadError`] nas seguintes situações:
+///
+/// * [`DownloadError::UnsafePath`]: `filename` contém componentes `..`, é um caminho absoluto
+///   ou possui um prefixo de unidade (Windows), o que faria o arquivo ser salvo fora de `dest_dir`.
+///   Neste caso nenhuma requisição é enviada.
+/// * [`DownloadError::NotADirectory`]: `dest_dir` já existe, mas não é um diretório. Também
+///   é verificado antes de enviar a requisição.
+/// * [`DownloadError::Request`]: falha ao enviar a requisição GET inicial (ex: erro de DNS,
 ///   falha de conexão de rede).
-/// * Se o servidor responder com um status de erro HTTP (4xx ou 5xx).
-///     * Especificamente para um erro 404 (Not Found), uma mensagem customizada de pânico será exibida.
-///     * Para outros erros HTTP, uma mensagem de pânico detalhando o status e o erro será mostrada.
-/// * Se houver falha ao criar o diretório "downloads".
-/// * Se houver falha ao criar o arquivo local onde o conteúdo será salvo.
-/// * Se houver falha ao copiar o conteúdo da resposta HTTP para o arquivo local.
+/// * [`DownloadError::Timeout`]: o tempo limite de conexão ou da requisição foi excedido,
+///   inclusive durante a leitura do corpo da resposta.
+/// * [`DownloadError::NotFound`]: o servidor respondeu com 404 (Not Found).
+/// * [`DownloadError::HttpStatus`]: o servidor respondeu com outro status de erro HTTP (4xx ou 5xx).
+/// * [`DownloadError::CreateDir`]: falha ao criar o diretório `dest_dir` ou um de seus subdiretórios.
+/// * [`DownloadError::CreateFile`]: falha ao criar o arquivo local onde o conteúdo será salvo.
+/// * [`DownloadError::Copy`]: falha ao copiar o conteúdo da resposta HTTP para o arquivo local.
+pub fn download_file_to(url: &str, filename: &str, dest_dir: &Path) -> Result<u64, DownloadError> {
+    let client = DownloadConfig::default().build_client()?;
+    let options = DownloadOptions::default();
+    download_impl(&client, url, filename, dest_dir, &options, &mut |_, _| {})
+}
+
+/// Realiza o download de um arquivo para `dest`, usando um `Client` já construído.
+///
+/// Reaproveitar o mesmo `Client` em vários downloads (inclusive entre threads) permite
+/// que eles compartilhem o pool de conexões, e aplica a todos os tempos limite com que
+/// ele foi configurado (veja [`DownloadConfig::build_client`]).
+///
+/// # Argumentos
+///
+/// * `client`: O `Client` bloqueante usado para enviar a requisição.
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest`.
+/// * `dest`: O diretório onde o arquivo será salvo.
+///
+/// # Retorno
+///
+/// O número de bytes gravados no arquivo local, em caso de sucesso.
+///
+/// # Erros
+///
+/// Os mesmos de [`download_file_to`].
+///
+/// # Exemplos
+///
+/// ```no_run
+/// use std::path::Path;
+/// use std::time::Duration;
+/// use test_thread_download::config::DownloadConfig;
+/// use test_thread_download::download::download_file_with;
+///
+/// let config = DownloadConfig {
+///     timeout: Some(Duration::from_secs(10)),
+///     ..Default::default()
+/// };
+/// let client = config.build_client()?;
+/// for filename in ["arquivo_1.jpg", "arquivo_2.jpg"] {
+///     download_file_with(&client, "https://royalcast.com.br/files", filename, Path::new("downloads"))?;
+/// }
+/// # Ok::<(), test_thread_download::error::DownloadError>(())
+/// ```
+pub fn download_file_with(
+    client: &Client,
+    url: &str,
+    filename: &str,
+    dest: &Path,
+) -> Result<u64, DownloadError> {
+    let options = DownloadOptions::default();
+    download_impl(client, url, filename, dest, &options, &mut |_, _| {})
+}
+
+/// Realiza o download de um arquivo para `dest_dir`, opcionalmente retomando um
+/// download parcial deixado por uma execução anterior.
+///
+/// Com `resume` igual a `true` e um arquivo local já existente, envia o cabeçalho
+/// `Range: bytes=<tamanho_local>-` e trata a resposta do servidor assim:
+///
+/// * `206 Partial Content`: os bytes restantes são acrescentados ao final do arquivo local.
+///   Se o `Content-Range` da resposta não começar no tamanho local, o arquivo é
+///   baixado novamente do zero, sem `Range`, para não corrompê-lo.
+/// * `200 OK` (o servidor não suporta ranges): o arquivo é truncado e baixado do zero.
+/// * `416 Range Not Satisfiable`: se o `Content-Range` (`bytes */N`) informar um tamanho
+///   remoto igual ao local, o arquivo é considerado completo e nada é baixado. Caso
+///   contrário (ex: o arquivo remoto diminuiu), o arquivo é baixado novamente do zero.
+///
+/// Com `resume` igual a `false`, o comportamento é idêntico ao de [`download_file_to`].
+///
+/// # Argumentos
+///
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest_dir`.
+/// * `dest_dir`: O diretório onde o arquivo será salvo.
+/// * `resume`: Se `true`, tenta aproveitar os bytes já presentes no arquivo local.
+///
+/// # Retorno
+///
+/// O número de bytes gravados no arquivo local nesta chamada (`0` se ele já estava completo).
+///
+/// # Erros
+///
+/// Os mesmos de [`download_file_to`].
+pub fn download_file_resumable(
+    url: &str,
+    filename: &str,
+    dest_dir: &Path,
+    resume: bool,
+) -> Result<u64, DownloadError> {
+    let client = DownloadConfig::default().build_client()?;
+    let options = DownloadOptions {
+        resume,
+        ..Default::default()
+    };
+    download_impl(&client, url, filename, dest_dir, &options, &mut |_, _| {})
+}
+
+/// Realiza o download de um arquivo para `dest_dir`, informando o progresso a cada
+/// bloco recebido.
+///
+/// O callback `on_progress` é chamado após cada bloco gravado (de até 64 KiB) com
+/// `(bytes_baixados_até_agora, total)`, onde `total` vem do cabeçalho `Content-Length`
+/// da resposta e é `None` quando o servidor não o envia. Isso permite, por exemplo,
+/// alimentar uma barra de progresso sem que esta biblioteca dependa de uma.
+///
+/// # Argumentos
+///
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest_dir`.
+/// * `dest_dir`: O diretório onde o arquivo será salvo.
+/// * `on_progress`: Callback invocado a cada bloco com o progresso do download.
+///
+/// # Retorno
+///
+/// O número de bytes gravados no arquivo local, em caso de sucesso.
+///
+/// # Erros
+///
+/// Os mesmos de [`download_file_to`].
 ///
 /// # Exemplos
 ///
 /// ```no_run
-/// // Supondo que esta função esteja acessível (ex: no mesmo módulo ou importada)
-/// // fn download_file(url: &str, filename: &str) { /* ... */ }
+/// use std::path::Path;
+/// use test_thread_download::download::download_file_with_progress;
+///
+/// let result = download_file_with_progress(
+///     "https://royalcast.com.br/files",
+///     "arquivo_1.jpg",
+///     Path::new("downloads"),
+///     |downloaded, total| match total {
+///         Some(total) => println!("{}/{} bytes", downloaded, total),
+///         None => println!("{} bytes", downloaded),
+///     },
+/// );
+/// ```
+pub fn download_file_with_progress(
+    url: &str,
+    filename: &str,
+    dest_dir: &Path,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<u64, DownloadError> {
+    let client = DownloadConfig::default().build_client()?;
+    let options = DownloadOptions::default();
+    download_impl(&client, url, filename, dest_dir, &options, &mut on_progress)
+}
+
+/// Realiza o download de um arquivo para `dest` e verifica seu conteúdo contra um
+/// checksum SHA-256 esperado.
+///
+/// O conteúdo é gravado em um arquivo temporário ao lado do destino (ex: "a.jpg.part")
+/// e o hash é calculado incrementalmente, à medida que os bytes são gravados, sem reler
+/// o arquivo depois. Só depois que o digest confere o arquivo temporário é renomeado
+/// para o nome final. Se o download falhar ou o digest não corresponder, o arquivo
+/// temporário é removido: um arquivo corrompido ou truncado nunca ocupa o destino, e um
+/// arquivo que já existia nele é mantido intacto.
+///
+/// # Argumentos
+///
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest`.
+/// * `dest`: O diretório onde o arquivo será salvo.
+/// * `expected_sha256`: O digest SHA-256 esperado, em hexadecimal (maiúsculas ou minúsculas).
+///
+/// # Retorno
+///
+/// O número de bytes gravados no arquivo local, em caso de sucesso.
+///
+/// # Erros
+///
+/// Os mesmos de [`download_file_to`], além de:
+///
+/// * [`DownloadError::InvalidChecksum`]: `expected_sha256` não tem 64 dígitos hexadecimais.
+///   Neste caso nenhuma requisição é enviada.
+/// * [`DownloadError::ChecksumMismatch`]: o digest do conteúdo baixado difere de `expected_sha256`.
+pub fn download_file_checked(
+    url: &str,
+    filename: &str,
+    dest: &Path,
+    expected_sha256: &str,
+) -> Result<u64, DownloadError> {
+    let client = DownloadConfig::default().build_client()?;
+    let options = DownloadOptions {
+        expected_sha256: Some(expected_sha256.to_string()),
+        ..Default::default()
+    };
+    download_impl(&client, url, filename, dest, &options, &mut |_, _| {})
+}
+
+/// Resultado de um download que pode ser pulado (veja [`download_file_or_skip`] e
+/// [`DownloadOptions::skip_existing`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadOutcome {
+    /// O arquivo foi baixado; contém o número de bytes gravados.
+    Downloaded(u64),
+    /// O arquivo já existia no destino (e não estava vazio), então não foi baixado.
+    Skipped,
+}
+
+/// Realiza o download de um arquivo para `dest_dir`, a menos que ele já exista.
+///
+/// Com `overwrite` igual a `false`, um arquivo regular não vazio já presente no destino
+/// faz com que o download seja pulado, sem enviar nenhuma requisição. Um arquivo vazio
+/// (ex: deixado por uma execução anterior que falhou) não é considerado completo e é
+/// baixado novamente. Com `overwrite` igual a `true`, o arquivo é sempre baixado e
+/// sobrescrito, como em [`download_file_to`].
+///
+/// # Argumentos
+///
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest_dir`.
+/// * `dest_dir`: O diretório onde o arquivo será salvo.
+/// * `overwrite`: Se `true`, baixa e sobrescreve mesmo que o arquivo já exista.
+///
+/// # Retorno
+///
+/// [`DownloadOutcome::Downloaded`] com o número de bytes gravados, ou
+/// [`DownloadOutcome::Skipped`] se o arquivo já existia.
+///
+/// # Erros
+///
+/// Os mesmos de [`download_file_to`].
+pub fn download_file_or_skip(
+    url: &str,
+    filename: &str,
+    dest_dir: &Path,
+    overwrite: bool,
+) -> Result<DownloadOutcome, DownloadError> {
+    let client = DownloadConfig::default().build_client()?;
+    let options = DownloadOptions {
+        skip_existing: !overwrite,
+        ..Default::default()
+    };
+    download_file_with_options(&client, url, filename, dest_dir, &options, |_, _| {})
+}
+
+/// Opções de um download feito com [`download_file_with_options`].
+///
+/// Permitem combinar os comportamentos das funções de conveniência (ex: retomar um
+/// download e informar o progresso) com um `Client` configurado por [`DownloadConfig`].
+/// O valor padrão equivale a [`download_file_with`].
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Aproveita os bytes já presentes no arquivo local (veja [`download_file_resumable`]).
+    pub resume: bool,
+    /// Digest SHA-256 esperado do conteúdo, em hexadecimal (veja [`download_file_checked`]).
+    /// Quando informado, `resume` é ignorado e o arquivo é sempre baixado do zero, já que
+    /// apenas os bytes recebidos na chamada poderiam ser verificados.
+    pub expected_sha256: Option<String>,
+    /// Pula o download, sem enviar nenhuma requisição, se já existir um arquivo regular não
+    /// vazio no destino (veja [`download_file_or_skip`]).
+    pub skip_existing: bool,
+}
+
+/// Realiza o download de um arquivo para `dest` com um `Client` já construído,
+/// combinando livremente as opções de `options`.
+///
+/// É o ponto de entrada mais geral da biblioteca: as demais funções `download_file_*`
+/// equivalem a chamá-la com um `Client` padrão e uma única opção ativada.
 ///
-/// fn main() {
-///     // Exemplo de download de um arquivo (substitua com uma URL e arquivo válidos para teste)
-///     // Este exemplo provavelmente falhará se o arquivo não existir, causando um pânico.
-///     // download_file("[https://exemplo.com/dados](https://exemplo.com/dados)", "meuarquivo.txt");
+/// # Argumentos
+///
+/// * `client`: O `Client` bloqueante usado para enviar a requisição.
+/// * `url`: A URL base de onde o arquivo será baixado.
+/// * `filename`: O nome do arquivo a ser baixado e salvo em `dest`.
+/// * `dest`: O diretório onde o arquivo será salvo.
+/// * `options`: Quais comportamentos (retomada, checksum, pular existentes) aplicar.
+/// * `on_progress`: Callback invocado a cada bloco, como em [`download_file_with_progress`].
+///
+/// # Retorno
+///
+/// [`DownloadOutcome::Downloaded`] com o número de bytes gravados nesta chamada, ou
+/// [`DownloadOutcome::Skipped`] se `options.skip_existing` estava ativado e o arquivo já existia.
+///
+/// # Erros
+///
+/// Os mesmos de [`download_file_to`] e de [`download_file_checked`].
+///
+/// # Exemplos
 ///
-///     // Exemplo com um arquivo que pode existir (usado para testes de API pública)
-///     // Note que "todos/1" será salvo como "1" no diretório "downloads".
-///     // download_file("[https://jsonplaceholder.typicode.com](https://jsonplaceholder.typicode.com)", "todos/1");
+/// ```no_run
+/// use std::path::Path;
+/// use std::time::Duration;
+/// use test_thread_download::config::DownloadConfig;
+/// use test_thread_download::download::{download_file_with_options, DownloadOptions};
+///
+/// let client = DownloadConfig {
+///     timeout: Some(Duration::from_secs(10)),
+///     ..Default::default()
 /// }
+/// .build_client()?;
+/// let options = DownloadOptions {
+///     resume: true,
+///     ..Default::default()
+/// };
+/// download_file_with_options(
+///     &client,
+///     "https://royalcast.com.br/files",
+///     "arquivo_1.jpg",
+///     Path::new("downloads"),
+///     &options,
+///     |downloaded, total| println!("{} de {:?} bytes", downloaded, total),
+/// )?;
+/// # Ok::<(), test_thread_download::error::DownloadError>(())
 /// ```
-pub fn download_file(url: &str, filename: &str) {
+pub fn download_file_with_options(
+    client: &Client,
+    url: &str,
+    filename: &str,
+    dest: &Path,
+    options: &DownloadOptions,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<DownloadOutcome, DownloadError> {
+    if options.skip_existing {
+        let local_file_path = dest.join(safe_relative_path(filename)?);
+        if is_present(&local_file_path) {
+            println!(
+                "Arquivo '{}' já existe em '{}'; download pulado.",
+                filename,
+                local_file_path.display()
+            );
+            return Ok(DownloadOutcome::Skipped);
+        }
+    }
+
+    download_impl(client, url, filename, dest, options, &mut on_progress)
+        .map(DownloadOutcome::Downloaded)
+}
+
+/// Indica se já existe um download completo em `path`: um arquivo regular não vazio.
+///
+/// Um arquivo vazio (ex: deixado por uma execução anterior que falhou) não conta como
+/// presente. É o critério comum de [`download_file_or_skip`] e [`missing`].
+fn is_present(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+}
+
+/// Implementação comum de [`download_file_with_options`] e das funções de conveniência
+/// que não pulam arquivos existentes. `options.skip_existing` é tratado pelo chamador.
+///
+/// Ao retomar um download, o progresso informado a `on_progress` inclui os bytes que
+/// já estavam no arquivo local, tanto no valor baixado quanto no total.
+fn download_impl(
+    client: &Client,
+    url: &str,
+    filename: &str,
+    dest_dir: &Path,
+    options: &DownloadOptions,
+    on_progress: &mut dyn FnMut(u64, Option<u64>),
+) -> Result<u64, DownloadError> {
+    // Valida o nome do arquivo antes de qualquer requisição, para nunca gravar fora de `dest_dir`.
+    let relative_path = safe_relative_path(filename)?;
+
+    // Um digest malformado nunca conferiria; é rejeitado antes de baixar o arquivo inteiro.
+    if let Some(expected) = &options.expected_sha256 {
+        if !is_sha256_hex(expected.trim()) {
+            return Err(DownloadError::InvalidChecksum {
+                expected: expected.clone(),
+            });
+        }
+    }
+
+    // Um arquivo regular (ou outro tipo de entrada) no lugar do diretório de destino faria
+    // `create_dir_all` falhar com uma mensagem confusa; reporta o caso explicitamente, antes
+    // de gastar uma requisição.
+    if dest_dir.exists() && !dest_dir.is_dir() {
+        return Err(DownloadError::NotADirectory {
+            path: dest_dir.to_path_buf(),
+        });
+    }
+
     // Constrói a URL completa do arquivo combinando a URL base e o nome do arquivo.
     let file_url = format!("{}/{}", url, filename);
 
+    // Define o caminho completo para o arquivo local, incluindo o diretório de destino.
+    let local_file_path = dest_dir.join(&relative_path);
+
+    // Quantos bytes de um download anterior já estão no arquivo local. Sem `resume`,
+    // ou se o arquivo não existir, o download começa do zero.
+    let mut existing_len = if options.resume && options.expected_sha256.is_none() {
+        std::fs::metadata(&local_file_path)
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .map_or(0, |metadata| metadata.len())
+    } else {
+        0
+    };
+
     // Envia uma requisição GET bloqueante para o servidor para obter o arquivo.
     // A chamada é bloqueante, o que significa que a thread atual esperará pela resposta.
-    let response_result: Result<Response, reqwest::Error> = reqwest::blocking::get(&file_url);
+    // Ao retomar, pede apenas os bytes a partir do tamanho local.
+    // 1. Erros na própria requisição HTTP (antes de obter uma resposta), como erros de
+    //    rede ou falhas de DNS, são propagados como `DownloadError::Request`, e um tempo
+    //    limite excedido como `DownloadError::Timeout`.
+    let mut request = client.get(&file_url);
+    if existing_len > 0 {
+        request = request.header(RANGE, format!("bytes={}-", existing_len));
+    }
+    let mut http_response: Response = request.send()?;
 
-    // 1. Trata erros potenciais na própria requisição HTTP (antes de obter uma resposta).
-    //    Isso inclui erros de rede, falhas de DNS, etc.
-    let http_response: Response = match response_result {
-        Ok(r) => {
-            // Requisição bem-sucedida em termos de comunicação, recebemos uma resposta.
-            r
-        }
-        Err(e) => {
-            // A requisição falhou em um nível fundamental (ex: rede).
-            // Entra em pânico com uma mensagem de erro detalhada.
-            panic!(
-                "Falha ao enviar requisição GET para a URL '{}': {}",
-                file_url, e
-            );
-        }
-    };
+    // O range pedido começa no fim do arquivo remoto. O arquivo local só está completo se
+    // o tamanho remoto informado (`Content-Range: bytes */N`) for igual ao local.
+    let status = http_response.status();
+    let remote_len = content_range_total(http_response.headers());
+    if existing_len > 0
+        && status == StatusCode::RANGE_NOT_SATISFIABLE
+        && remote_len == Some(existing_len)
+    {
+        println!(
+            "Arquivo '{}' já está completo em '{}' ({} bytes).",
+            filename,
+            local_file_path.display(),
+            existing_len
+        );
+        return Ok(0);
+    }
+
+    // Nos demais casos de 416 (ex: o arquivo remoto diminuiu ou mudou), ou em um 206 cujo
+    // range não começa no fim do arquivo local, acrescentar os bytes corromperia o arquivo;
+    // o download é refeito do zero, sem `Range`.
+    let restart = existing_len > 0
+        && (status == StatusCode::RANGE_NOT_SATISFIABLE
+            || (status == StatusCode::PARTIAL_CONTENT
+                && content_range_start(http_response.headers()) != Some(existing_len)));
+    if restart {
+        println!(
+            "O servidor não pôde retomar '{}' a partir de {} bytes; baixando o arquivo do zero.",
+            filename, existing_len
+        );
+        existing_len = 0;
+        http_response = client.get(&file_url).send()?;
+    }
 
     // 2. Verifica o status HTTP da resposta recebida.
-    //    O método `error_for_status()` consome a `http_response` e retorna:
-    //    - `Ok(Response)` se o status HTTP for de sucesso (2xx).
-    //    - `Err(reqwest::Error)` se o status HTTP for de erro (4xx ou 5xx).
-    //      Neste caso, o `reqwest::Error` conterá informações sobre o status de erro.
-    let mut successful_response: Response = match http_response.error_for_status() {
-        Ok(resp_ok) => {
-            // O status HTTP indica sucesso (ex: 200 OK).
-            // A `resp_ok` é a mesma resposta, agora confirmada como bem-sucedida.
-            resp_ok
-        }
-        Err(err_with_status) => {
-            // O servidor respondeu com um código de status de erro HTTP.
-            // `err_with_status` é um `reqwest::Error` que encapsula este erro de status.
-            if err_with_status.status() == Some(StatusCode::NOT_FOUND) {
-                // Trata especificamente o erro 404 (Not Found).
-                panic!(
-                    "Arquivo '{}' não encontrado na URL '{}'. O servidor retornou 404 Not Found.",
-                    filename, file_url
-                );
-            } else {
-                // Trata outros erros HTTP (4xx ou 5xx).
-                panic!(
-                    "Erro HTTP ao tentar baixar o arquivo '{}' da URL '{}'. Status: {:?}. Detalhes: {}",
-                    filename,
-                    file_url,
-                    err_with_status.status(), // Exibe o código de status (ex: Some(500))
-                    err_with_status           // Exibe os detalhes completos do reqwest::Error
-                );
+    check_status(http_response.status(), filename)?;
+    let mut successful_response = http_response;
+
+    // Cria o diretório de destino se ele ainda não existir.
+    // `create_dir_all` cria todos os diretórios pais necessários e não falha se o diretório já existir.
+    std::fs::create_dir_all(dest_dir).map_err(DownloadError::CreateDir)?;
+
+    // Cria os subdiretórios intermediários de nomes aninhados (ex: "todos" em "todos/1").
+    if let Some(parent) = local_file_path.parent() {
+        std::fs::create_dir_all(parent).map_err(DownloadError::CreateDir)?;
+    }
+
+    // Só acrescenta ao arquivo existente se o servidor aceitou o range (206). Um 200
+    // significa que o corpo contém o arquivo inteiro, então ele é baixado do zero.
+    let append = existing_len > 0 && successful_response.status() == StatusCode::PARTIAL_CONTENT;
+
+    // Um download verificado grava em um arquivo temporário ao lado do destino, que só
+    // substitui o arquivo final depois que o digest confere. Assim, bytes não verificados
+    // nunca ocupam o destino, e um arquivo que já existia nele não é truncado.
+    let write_path = if options.expected_sha256.is_some() {
+        part_path(&local_file_path)
+    } else {
+        local_file_path.clone()
+    };
+
+    // Abre o arquivo local para acrescentar os bytes restantes, ou cria (sobrescrevendo,
+    // se já existir) o arquivo onde o conteúdo será salvo.
+    let open_result = if append {
+        OpenOptions::new().append(true).open(&write_path)
+    } else {
+        File::create(&write_path)
+    };
+    let mut local_file = open_result.map_err(DownloadError::CreateFile)?;
+
+    // O progresso de um download retomado conta a partir dos bytes já existentes.
+    let offset = if append { existing_len } else { 0 };
+    let total = successful_response
+        .content_length()
+        .map(|content_length| offset + content_length);
+
+    // O hash, quando pedido, é atualizado com cada bloco durante a própria cópia.
+    let mut hasher = options.expected_sha256.as_ref().map(|_| Sha256::new());
+
+    // Copia o conteúdo da resposta HTTP (que foi confirmada como bem-sucedida)
+    // para o arquivo local, bloco a bloco, informando o progresso após cada um.
+    let copy_result = copy_with_progress(
+        &mut successful_response,
+        &mut local_file,
+        |chunk, copied| {
+            if let Some(hasher) = hasher.as_mut() {
+                hasher.update(chunk);
             }
+            on_progress(offset + copied, total)
+        },
+    );
+    let bytes_copied = match copy_result {
+        Ok(bytes_copied) => bytes_copied,
+        Err(e) => {
+            // Um download verificado descarta o arquivo temporário incompleto.
+            if options.expected_sha256.is_some() {
+                drop(local_file);
+                let _ = std::fs::remove_file(&write_path);
+            }
+            // O tempo limite da requisição também vale para a leitura do corpo.
+            return Err(if is_timeout(&e) {
+                DownloadError::Timeout
+            } else {
+                DownloadError::Copy(e)
+            });
         }
     };
 
-    // Define o nome do diretório onde os arquivos baixados serão salvos.
-    let download_dir_name = "downloads";
-    let download_path = Path::new(download_dir_name);
+    // Compara o digest calculado com o esperado. Se forem diferentes, descarta o arquivo
+    // temporário; se conferirem, ele passa a ser o arquivo final.
+    if let (Some(expected), Some(hasher)) = (&options.expected_sha256, hasher) {
+        let expected = expected.trim().to_lowercase();
+        let actual = to_hex(&hasher.finalize());
+        drop(local_file);
+        if actual != expected {
+            let _ = std::fs::remove_file(&write_path);
+            return Err(DownloadError::ChecksumMismatch { expected, actual });
+        }
+        if let Err(e) = std::fs::rename(&write_path, &local_file_path) {
+            let _ = std::fs::remove_file(&write_path);
+            return Err(DownloadError::CreateFile(e));
+        }
+    }
 
-    // Cria o diretório "downloads" se ele ainda não existir.
-    // `create_dir_all` cria todos os diretórios pais necessários e não falha se o diretório já existir.
-    // Entra em pânico se houver uma falha na criação do diretório (ex: permissões).
-    if let Err(e) = std::fs::create_dir_all(download_path) {
-        panic!(
-            "Falha ao criar o diretório '{}': {}",
-            download_path.display(),
-            e
+    // Conteúdo copiado com sucesso. Imprime uma mensagem de sucesso.
+    if append {
+        println!(
+            "Download do arquivo '{}' para '{}' retomado a partir de {} bytes ({} bytes) concluído com sucesso!",
+            filename,
+            local_file_path.display(),
+            existing_len,
+            bytes_copied
+        );
+    } else {
+        println!(
+            "Download do arquivo '{}' para '{}' ({} bytes) concluído com sucesso!",
+            filename,
+            local_file_path.display(),
+            bytes_copied
         );
     }
 
-    // Define o caminho completo para o arquivo local, incluindo o diretório "downloads".
-    let local_file_path = download_path.join(filename);
+    Ok(bytes_copied)
+}
 
-    // Cria (ou sobrescreve, se já existir) o arquivo local onde o conteúdo será salvo.
-    // Entra em pânico se houver falha na criação do arquivo (ex: permissões, caminho inválido).
-    let mut local_file = match File::create(&local_file_path) {
-        Ok(file) => file,
-        Err(e) => {
-            panic!(
-                "Falha ao criar o arquivo local '{}': {}",
-                local_file_path.display(),
-                e
-            );
-        }
-    };
+/// Copia todo o conteúdo de `reader` para `writer` usando um buffer de [`BUFFER_SIZE`] bytes.
+///
+/// Diferente de `io::copy`, chama `on_chunk` após cada bloco gravado, com o próprio bloco
+/// e o total de bytes copiados até o momento. Leituras e escritas interrompidas
+/// (`ErrorKind::Interrupted`, comuns em NFS/SMB) são repetidas, as escritas pelo próprio
+/// `write_all`, sem refazer o download; os demais erros de escrita (ex: disco cheio,
+/// permissão negada) são permanentes e encerram a cópia.
+fn copy_with_progress(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    mut on_chunk: impl FnMut(&[u8], u64),
+) -> io::Result<u64> {
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    let mut copied: u64 = 0;
 
-    // Copia o conteúdo da resposta HTTP (que foi confirmada como bem-sucedida)
-    // para o arquivo local. A função `io::copy` lê de `successful_response`
-    // (que implementa `Read`) e escreve em `local_file` (que implementa `Write`).
-    // Entra em pânico se houver erro durante a cópia (ex: disco cheio, conexão interrompida).
-    match std::io::copy(&mut successful_response, &mut local_file) {
-        Ok(bytes_copied) => {
-            // Conteúdo copiado com sucesso. Imprime uma mensagem de sucesso.
-            println!(
-                "Download do arquivo '{}' para '{}' ({} bytes) concluído com sucesso!",
-                filename,
-                local_file_path.display(),
-                bytes_copied
-            );
+    loop {
+        let bytes_read = match reader.read(&mut buffer) {
+            // Fim do corpo da resposta.
+            Ok(0) => break,
+            Ok(bytes_read) => bytes_read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        writer.write_all(&buffer[..bytes_read])?;
+        copied += bytes_read as u64;
+        on_chunk(&buffer[..bytes_read], copied);
+    }
+
+    Ok(copied)
+}
+
+/// Extrai a posição do primeiro byte do cabeçalho `Content-Range` de uma resposta 206
+/// (ex: `100` em "bytes 100-199/200"). Retorna `None` se o cabeçalho faltar ou for inválido.
+fn content_range_start(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (start, _) = value.trim().strip_prefix("bytes ")?.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+/// Extrai o tamanho total do recurso do cabeçalho `Content-Range` (ex: `200` em
+/// "bytes 100-199/200" ou em "bytes */200", a forma usada em respostas 416). Retorna `None`
+/// se o cabeçalho faltar, for inválido ou o tamanho for desconhecido (`*`).
+fn content_range_total(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let (_, total) = value.trim().strip_prefix("bytes ")?.rsplit_once('/')?;
+    total.trim().parse().ok()
+}
+
+/// Retorna o caminho do arquivo temporário usado por um download verificado: o próprio
+/// `path` com ".part" acrescentado ao nome (ex: "a.jpg" → "a.jpg.part").
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+/// Indica se `value` é um digest SHA-256 em hexadecimal: exatamente 64 dígitos hexadecimais.
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Formata `bytes` como uma string hexadecimal em letras minúsculas.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Indica se um erro de leitura do corpo da resposta foi causado por um tempo limite.
+///
+/// O `Response` bloqueante do reqwest reporta tempos limite como um `io::Error` que
+/// envolve um `reqwest::Error`, então ambos os casos são verificados.
+fn is_timeout(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::TimedOut
+        || e.get_ref()
+            .and_then(|inner| inner.downcast_ref::<reqwest::Error>())
+            .is_some_and(reqwest::Error::is_timeout)
+}
+
+/// Verifica o status HTTP de uma resposta, convertendo status de erro em [`DownloadError`].
+///
+/// Assim como `error_for_status()` do reqwest, considera erro qualquer status 4xx ou 5xx.
+/// É compartilhada pelos clientes bloqueante e assíncrono.
+pub(crate) fn check_status(status: StatusCode, filename: &str) -> Result<(), DownloadError> {
+    if status == StatusCode::NOT_FOUND {
+        // Trata especificamente o erro 404 (Not Found), para que o chamador
+        // possa identificá-lo sem analisar a mensagem de erro.
+        Err(DownloadError::NotFound {
+            filename: filename.to_string(),
+        })
+    } else if status.is_client_error() || status.is_server_error() {
+        // Trata outros erros HTTP (4xx ou 5xx).
+        Err(DownloadError::HttpStatus { status })
+    } else {
+        Ok(())
+    }
+}
+
+/// Converte `filename` em um caminho relativo que com certeza permanece dentro do
+/// diretório de destino.
+///
+/// São aceitos apenas componentes normais (ex: "todos", "1") e `.`, que é descartado.
+/// Componentes `..`, uma raiz (`/`) ou um prefixo de unidade do Windows (ex: `C:`)
+/// resultam em [`DownloadError::UnsafePath`], assim como um nome sem nenhum componente normal.
+pub(crate) fn safe_relative_path(filename: &str) -> Result<PathBuf, DownloadError> {
+    let mut relative_path = PathBuf::new();
+
+    for component in Path::new(filename).components() {
+        match component {
+            Component::Normal(part) => relative_path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(DownloadError::UnsafePath {
+                    filename: filename.to_string(),
+                });
+            }
         }
-        Err(e) => {
-            panic!(
-                "Falha ao copiar o conteúdo baixado para o arquivo '{}': {}",
-                local_file_path.display(),
-                e
-            );
+    }
+
+    // Um nome vazio (ou só com ".") apontaria para o próprio diretório de destino.
+    if relative_path.as_os_str().is_empty() {
+        return Err(DownloadError::UnsafePath {
+            filename: filename.to_string(),
+        });
+    }
+
+    Ok(relative_path)
+}
+
+/// Realiza o download de vários arquivos em paralelo, usando até `max_concurrency`
+/// threads de trabalho, e os salva no diretório local "downloads".
+///
+/// Equivale a chamar [`download_all_with`] com um `Client` padrão e
+/// `Path::new("downloads")` como destino.
+///
+/// # Argumentos
+///
+/// * `url`: A URL base de onde os arquivos serão baixados.
+/// * `filenames`: Os nomes dos arquivos a serem baixados.
+/// * `max_concurrency`: O número máximo de downloads simultâneos. O valor `0` é tratado como `1`.
+///
+/// # Retorno
+///
+/// O mesmo de [`download_all_with`].
+pub fn download_all(
+    url: &str,
+    filenames: &[&str],
+    max_concurrency: usize,
+) -> Vec<(String, Result<u64, DownloadError>)> {
+    // Um único `Client` para todas as threads, para que reaproveitem as conexões.
+    let download_dir = Path::new("downloads");
+    match DownloadConfig::default().build_client() {
+        Ok(client) => download_all_with(&client, url, filenames, download_dir, max_concurrency),
+        // Se o `Client` compartilhado não pôde ser criado, cada download tenta
+        // criar o seu e reporta o próprio erro.
+        Err(_) => download_each(filenames, max_concurrency, |filename| {
+            download_file_to(url, filename, download_dir)
+        }),
+    }
+}
+
+/// Realiza o download de vários arquivos em paralelo para `dest`, usando até
+/// `max_concurrency` threads de trabalho e um `Client` já construído.
+///
+/// Cada thread retira o próximo arquivo pendente de uma fila compartilhada (um índice
+/// atômico sobre `filenames`) e o baixa com [`download_file_with`], até que não reste
+/// nenhum. Todas as threads usam o mesmo `client`, compartilhando o pool de conexões e
+/// os tempos limite com que ele foi configurado.
+///
+/// # Argumentos
+///
+/// * `client`: O `Client` bloqueante usado por todos os downloads.
+/// * `url`: A URL base de onde os arquivos serão baixados.
+/// * `filenames`: Os nomes dos arquivos a serem baixados.
+/// * `dest`: O diretório onde os arquivos serão salvos.
+/// * `max_concurrency`: O número máximo de downloads simultâneos. O valor `0` é tratado como `1`.
+///
+/// # Retorno
+///
+/// Um `Vec` com um par `(filename, resultado)` para cada arquivo, na mesma ordem de `filenames`.
+/// Um pânico durante o download de um arquivo não interrompe os demais: ele é capturado e
+/// reportado como [`DownloadError::Panicked`] apenas para aquele arquivo.
+pub fn download_all_with(
+    client: &Client,
+    url: &str,
+    filenames: &[&str],
+    dest: &Path,
+    max_concurrency: usize,
+) -> Vec<(String, Result<u64, DownloadError>)> {
+    download_each(filenames, max_concurrency, |filename| {
+        download_file_with(client, url, filename, dest)
+    })
+}
+
+/// Executa `download` para cada nome de `filenames` em até `max_concurrency` threads,
+/// isolando pânicos por arquivo. Implementação comum de [`download_all`] e
+/// [`download_all_with`].
+fn download_each(
+    filenames: &[&str],
+    max_concurrency: usize,
+    download: impl Fn(&str) -> Result<u64, DownloadError> + Sync,
+) -> Vec<(String, Result<u64, DownloadError>)> {
+    // Não faz sentido criar mais threads do que arquivos a baixar.
+    let worker_count = max_concurrency.max(1).min(filenames.len());
+    let download = &download;
+
+    // Índice do próximo arquivo a ser baixado, compartilhado entre as threads.
+    let next_index = AtomicUsize::new(0);
+
+    // Cada thread envia `(índice, resultado)` por este canal ao concluir um arquivo.
+    let (result_tx, result_rx) = mpsc::channel();
+
+    // `thread::scope` permite que as threads tomem emprestados `download`, `filenames` e `next_index`,
+    // e garante que todas terminem antes de sair do bloco.
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..worker_count)
+            .map(|_| {
+                let result_tx = result_tx.clone();
+                let next_index = &next_index;
+                scope.spawn(move || loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    let Some(filename) = filenames.get(index) else {
+                        break;
+                    };
+
+                    // Captura um eventual pânico para que ele afete apenas este arquivo.
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| download(filename)))
+                        .unwrap_or_else(|payload| {
+                            Err(DownloadError::Panicked {
+                                message: panic_message(payload.as_ref()),
+                            })
+                        });
+
+                    // O receptor vive até o fim da função; o envio nunca falha aqui.
+                    let _ = result_tx.send((index, result));
+                })
+            })
+            .collect();
+
+        // Aguarda explicitamente todas as threads. Um pânico fora de `catch_unwind`
+        // é tratado abaixo, ao preencher os resultados ausentes.
+        for worker in workers {
+            let _ = worker.join();
         }
+    });
+
+    // Libera o transmissor original para que a iteração sobre o receptor termine.
+    drop(result_tx);
+
+    let mut results: Vec<Option<Result<u64, DownloadError>>> =
+        filenames.iter().map(|_| None).collect();
+    for (index, result) in result_rx {
+        results[index] = Some(result);
     }
+
+    filenames
+        .iter()
+        .zip(results)
+        .map(|(filename, result)| {
+            let result = result.unwrap_or_else(|| {
+                Err(DownloadError::Panicked {
+                    message: "a thread terminou sem reportar um resultado".to_string(),
+                })
+            });
+            (filename.to_string(), result)
+        })
+        .collect()
 }
 
-/*
-// Exemplo de como usar a função em um `main`
-fn main() {
-    println!("Iniciando o processo de download...");
+/// Extrai a mensagem de um pânico capturado, que normalmente é um `&str` ou uma `String`.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "pânico sem mensagem".to_string()
+    }
+}
 
-    // Teste 1: Tentar baixar um arquivo que provavelmente não existe (deve causar pânico com 404)
-    println!("\nTentativa 1: Baixando um arquivo inexistente...");
-    // Para observar o comportamento sem parar o programa, você precisaria
-    // de `std::panic::catch_unwind` ou modificar `download_file` para retornar `Result`.
-    // download_file("https://jsonplaceholder.typicode.com", "arquivo-que-nao-existe-12345.txt");
-    // Se a linha acima for descomentada, o programa provavelmente parará aqui.
+/// Retorna o tamanho, em bytes, do arquivo já presente localmente no diretório
+/// "downloads" para o `filename` informado.
+///
+/// Equivale a chamar [`local_progress_in`] com `Path::new("downloads")` como destino.
+///
+/// # Argumentos
+///
+/// * `filename`: O nome do arquivo, exatamente como passado para [`download_file`].
+///
+/// # Retorno
+///
+/// O mesmo de [`local_progress_in`].
+pub fn local_progress(filename: &str) -> Option<u64> {
+    local_progress_in(filename, Path::new("downloads"))
+}
 
-    // Teste 2: Tentar baixar um arquivo de uma URL base inválida (deve causar pânico na conexão)
-    // println!("\nTentativa 2: Baixando de uma URL base inválida...");
-    // download_file("https://dominio-inexistente-e-com-certeza-nao-funciona.com", "qualquercoisa.txt");
-    // Se a linha acima for descomentada, o programa provavelmente parará aqui.
+/// Retorna o tamanho, em bytes, do arquivo já presente localmente em `dest_dir`
+/// para o `filename` informado.
+///
+/// Útil para exibir quanto de um download já existe em disco antes de decidir
+/// baixá-lo novamente. O nome é validado como em [`download_file_to`], de modo que
+/// nunca é consultado um arquivo fora de `dest_dir`.
+///
+/// # Argumentos
+///
+/// * `filename`: O nome do arquivo, exatamente como passado para [`download_file_to`].
+/// * `dest_dir`: O diretório onde o arquivo baixado deveria estar.
+///
+/// # Retorno
+///
+/// * `Some(bytes)` se existir um arquivo regular no caminho local correspondente.
+/// * `None` se nada existir localmente, se o caminho não for um arquivo regular ou se
+///   `filename` apontar para fora de `dest_dir` (ex: "../../etc/passwd").
+pub fn local_progress_in(filename: &str, dest_dir: &Path) -> Option<u64> {
+    let local_file_path = dest_dir.join(safe_relative_path(filename).ok()?);
 
-    // Teste 3: Tentar baixar um arquivo que deve existir
-    println!("\nTentativa 3: Baixando um arquivo que deve existir...");
-    download_file("https://jsonplaceholder.typicode.com", "todos/1");
-    // Se bem-sucedido, você encontrará um arquivo chamado "1" no diretório "downloads".
+    // `metadata` segue links simbólicos; qualquer erro (ex: arquivo inexistente) resulta em `None`.
+    match std::fs::metadata(&local_file_path) {
+        Ok(metadata) if metadata.is_file() => Some(metadata.len()),
+        _ => None,
+    }
+}
 
-    println!("\nProcesso de download (ou tentativas) finalizado.");
+/// Retorna quais dos arquivos de `filenames` ainda não estão presentes em `dest_dir`.
+///
+/// Cada nome é validado como em [`download_file_to`] e verificado no caminho
+/// correspondente dentro de `dest_dir`, de modo que nomes aninhados (ex: "todos/1") são
+/// procurados no subdiretório correspondente. Assim como em [`download_file_or_skip`],
+/// um arquivo vazio (ex: deixado por uma execução anterior que falhou) não conta como
+/// presente.
+///
+/// # Argumentos
+///
+/// * `filenames`: Os nomes dos arquivos desejados, como seriam passados para [`download_file`].
+/// * `dest_dir`: O diretório onde os arquivos baixados deveriam estar.
+///
+/// # Retorno
+///
+/// Um `Vec<String>` com os nomes (na mesma ordem da entrada) para os quais não existe
+/// um arquivo regular não vazio no destino. Nomes que apontariam para fora de `dest_dir`
+/// (ex: "../x") nunca estão presentes e, portanto, também são retornados; baixá-los
+/// resulta em [`DownloadError::UnsafePath`].
+pub fn missing(filenames: &[&str], dest_dir: &Path) -> Vec<String> {
+    filenames
+        .iter()
+        .filter(|filename| {
+            !safe_relative_path(filename)
+                .is_ok_and(|relative_path| is_present(&dest_dir.join(relative_path)))
+        })
+        .map(|filename| filename.to_string())
+        .collect()
+}
+
+/// Agrupa uma lista de URLs completas pela sua base (esquema, host e porta).
+///
+/// Cada chave do mapa é a base da URL (ex: "https://royalcast.com.br") e cada valor
+/// é a lista dos caminhos correspondentes, sem a barra inicial (ex: "files/arquivo_1.jpg").
+/// Assim, cada par pode ser passado diretamente para [`download_file`] como `url` e
+/// `filename`.
+///
+/// Os caminhos são mantidos como aparecem na URL, ainda codificados com
+/// percent-encoding (ex: "files/a%20b.jpg"), para que `url/filename` reconstrua a URL
+/// requisitada; o arquivo local recebe esse mesmo nome. O fragmento (`#...`) nunca é
+/// enviado ao servidor e é descartado.
+///
+/// URLs com query (ex: "a.jpg?token=abc") são ignoradas: sem a query, URLs assinadas
+/// falhariam e URLs que diferem apenas na query gravariam no mesmo arquivo local; com
+/// ela, o nome do arquivo local conteria `?`, que não é permitido no Windows.
+///
+/// # Argumentos
+///
+/// * `urls`: As URLs completas a serem agrupadas.
+///
+/// # Retorno
+///
+/// Um `HashMap` de base → caminhos, preservando a ordem de entrada dentro de cada grupo.
+/// URLs inválidas, sem host (ex: "file:///tmp/x") ou com query são ignoradas.
+pub fn group_by_host(urls: &[&str]) -> HashMap<String, Vec<String>> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+
+    for url in urls {
+        let parsed = match Url::parse(url) {
+            Ok(parsed) if parsed.host_str().is_some() && parsed.query().is_none() => parsed,
+            _ => continue,
+        };
+
+        // `ascii_serialization` omite a porta quando ela é a padrão do esquema.
+        let base = parsed.origin().ascii_serialization();
+        let path = parsed.path().trim_start_matches('/').to_string();
+
+        groups.entry(base).or_default().push(path);
+    }
+
+    groups
+}
+
+/*
+// Exemplo de como usar a função em um `main`
+fn main() {
+    println!("Iniciando o processo de download...");
+
+    // Teste 1: Tentar baixar um arquivo que provavelmente não existe (deve retornar `DownloadError::NotFound`)
+    println!("\nTentativa 1: Baixando um arquivo inexistente...");
+    if let Err(e) = download_file("https://jsonplaceholder.typicode.com", "arquivo-que-nao-existe-12345.txt") {
+        println!("Falha esperada: {}", e);
+    }
+
+    // Teste 2: Tentar baixar um arquivo de uma URL base inválida (deve retornar `DownloadError::Request`)
+    println!("\nTentativa 2: Baixando de uma URL base inválida...");
+    if let Err(e) = download_file("https://dominio-inexistente-e-com-certeza-nao-funciona.com", "qualquercoisa.txt") {
+        println!("Falha esperada: {}", e);
+    }
+
+    // Teste 3: Tentar baixar um arquivo que deve existir
+    println!("\nTentativa 3: Baixando um arquivo que deve existir...");
+    if let Err(e) = download_file("https://jsonplaceholder.typicode.com", "todos/1") {
+        println!("Falha inesperada: {}", e);
+    }
+    // Se bem-sucedido, você encontrará um arquivo chamado "1" no diretório "downloads/todos".
+
+    println!("\nProcesso de download (ou tentativas) finalizado.");
+}
+*/
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+
+    /// Sobe um servidor HTTP local em uma porta livre e retorna sua URL base.
+    ///
+    /// Cada conexão é atendida em uma thread própria: `handle` recebe a requisição
+    /// (linha inicial e cabeçalhos) e escreve a resposta diretamente no `TcpStream`.
+    pub(crate) fn serve(handle: impl Fn(&str, &mut TcpStream) + Send + Sync + 'static) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = Arc::new(handle);
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let handle = Arc::clone(&handle);
+                thread::spawn(move || {
+                    let request = read_request(&stream);
+                    handle(&request, &mut stream);
+                });
+            }
+        });
+
+        url
+    }
+
+    /// Lê a requisição até a linha em branco que encerra os cabeçalhos.
+    fn read_request(stream: &TcpStream) -> String {
+        let mut reader = io::BufReader::new(stream);
+        let mut request = String::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                return request;
+            }
+            request.push_str(&line);
+        }
+    }
+
+    /// Escreve uma resposta HTTP completa. `Connection: close` evita que o `Client`
+    /// tente reaproveitar a conexão, que é atendida uma única vez.
+    pub(crate) fn respond(stream: &mut TcpStream, status: &str, headers: &[String], body: &[u8]) {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            status,
+            body.len()
+        );
+        for header in headers {
+            response.push_str(header);
+            response.push_str("\r\n");
+        }
+        response.push_str("\r\n");
+        let _ = stream.write_all(response.as_bytes());
+        let _ = stream.write_all(body);
+    }
+
+    /// Servidor que responde a qualquer requisição com `200 OK` e o corpo `content`.
+    pub(crate) fn serve_content(content: &'static [u8]) -> String {
+        serve(move |_, stream| respond(stream, "200 OK", &[], content))
+    }
+
+    /// Valor `N` de um cabeçalho `Range: bytes=N-` da requisição, se houver.
+    fn requested_range_start(request: &str) -> Option<usize> {
+        request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if !name.eq_ignore_ascii_case("range") {
+                return None;
+            }
+            value
+                .trim()
+                .strip_prefix("bytes=")?
+                .strip_suffix('-')?
+                .parse()
+                .ok()
+        })
+    }
+
+    /// Servidor que entrega `content` respeitando `Range: bytes=N-` (206 ou 416).
+    fn serve_ranged(content: Vec<u8>) -> String {
+        serve(
+            move |request, stream| match requested_range_start(request) {
+                Some(start) if start >= content.len() => {
+                    let content_range = format!("Content-Range: bytes */{}", content.len());
+                    respond(stream, "416 Range Not Satisfiable", &[content_range], b"")
+                }
+                Some(start) => {
+                    let content_range = format!(
+                        "Content-Range: bytes {}-{}/{}",
+                        start,
+                        content.len() - 1,
+                        content.len()
+                    );
+                    respond(
+                        stream,
+                        "206 Partial Content",
+                        &[content_range],
+                        &content[start..],
+                    )
+                }
+                None => respond(stream, "200 OK", &[], &content),
+            },
+        )
+    }
+
+    #[test]
+    fn download_file_to_saves_into_new_dest_dir() {
+        let url = serve_content(b"conteudo");
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("saida");
+
+        let bytes = download_file_to(&url, "arquivo.txt", &dest).unwrap();
+
+        assert_eq!(bytes, 8);
+        assert_eq!(
+            std::fs::read(dest.join("arquivo.txt")).unwrap(),
+            b"conteudo"
+        );
+    }
+
+    #[test]
+    fn download_file_to_rejects_a_file_as_dest_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("arquivo_regular");
+        std::fs::write(&dest, b"").unwrap();
+
+        // A verificação acontece antes da requisição, então a URL não precisa existir.
+        let result = download_file_to("http://127.0.0.1:1", "arquivo.txt", &dest);
+
+        assert!(
+            matches!(&result, Err(DownloadError::NotADirectory { path }) if *path == dest),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn safe_relative_path_rejects_names_outside_dest_dir() {
+        for filename in ["..", "../escape", "a/../../b", "/abs", "", "."] {
+            let result = safe_relative_path(filename);
+            assert!(
+                matches!(&result, Err(DownloadError::UnsafePath { filename: f }) if f == filename),
+                "{:?} -> {:?}",
+                filename,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn safe_relative_path_accepts_nested_names_and_drops_cur_dir() {
+        assert_eq!(
+            safe_relative_path("a/./b/c.jpg").unwrap(),
+            Path::new("a").join("b").join("c.jpg")
+        );
+    }
+
+    #[test]
+    fn download_file_to_rejects_escape_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("dest");
+
+        // Nenhuma requisição é enviada, então a URL não precisa existir.
+        let result = download_file_to("http://127.0.0.1:1", "../escape", &dest);
+
+        assert!(
+            matches!(result, Err(DownloadError::UnsafePath { .. })),
+            "{:?}",
+            result
+        );
+        assert!(!dest.exists());
+        assert!(!dir.path().join("escape").exists());
+    }
+
+    #[test]
+    fn download_file_to_creates_intermediate_dirs() {
+        let url = serve_content(b"imagem");
+        let dir = tempfile::tempdir().unwrap();
+
+        download_file_to(&url, "a/b/c.jpg", dir.path()).unwrap();
+
+        assert!(dir.path().join("a").join("b").is_dir());
+        assert_eq!(
+            std::fs::read(dir.path().join("a/b/c.jpg")).unwrap(),
+            b"imagem"
+        );
+    }
+
+    #[test]
+    fn resume_fetches_only_the_remaining_bytes() {
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let url = serve_ranged(content.clone());
+        let dir = tempfile::tempdir().unwrap();
+        let local_file_path = dir.path().join("dados.bin");
+
+        download_file_resumable(&url, "dados.bin", dir.path(), true).unwrap();
+        // Simula um download interrompido truncando o arquivo.
+        File::options()
+            .write(true)
+            .open(&local_file_path)
+            .unwrap()
+            .set_len(50_000)
+            .unwrap();
+
+        let bytes = download_file_resumable(&url, "dados.bin", dir.path(), true).unwrap();
+
+        assert_eq!(bytes, 150_000);
+        assert_eq!(std::fs::read(&local_file_path).unwrap(), content);
+
+        // Com o arquivo completo, o servidor responde 416 e nada é baixado.
+        let bytes = download_file_resumable(&url, "dados.bin", dir.path(), true).unwrap();
+        assert_eq!(bytes, 0);
+        assert_eq!(std::fs::read(&local_file_path).unwrap(), content);
+    }
+
+    #[test]
+    fn resume_restarts_when_content_range_does_not_match() {
+        // Servidor que responde a qualquer `Range` com o arquivo inteiro, em um 206.
+        let url = serve(|request, stream| {
+            if requested_range_start(request).is_some() {
+                let content_range = "Content-Range: bytes 0-9/10".to_string();
+                respond(
+                    stream,
+                    "206 Partial Content",
+                    &[content_range],
+                    b"0123456789",
+                )
+            } else {
+                respond(stream, "200 OK", &[], b"0123456789")
+            }
+        });
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("dados.txt"), b"01234").unwrap();
+
+        let bytes = download_file_resumable(&url, "dados.txt", dir.path(), true).unwrap();
+
+        assert_eq!(bytes, 10);
+        assert_eq!(
+            std::fs::read(dir.path().join("dados.txt")).unwrap(),
+            b"0123456789"
+        );
+    }
+
+    /// `Client` com um tempo limite curto, para os testes de servidores que travam.
+    fn client_with_short_timeout() -> Client {
+        DownloadConfig {
+            timeout: Some(std::time::Duration::from_millis(300)),
+            ..Default::default()
+        }
+        .build_client()
+        .unwrap()
+    }
+
+    #[test]
+    fn timeout_when_server_never_responds() {
+        // Aceita a conexão, mas nunca responde.
+        let url = serve(|_, _| thread::sleep(std::time::Duration::from_secs(10)));
+        let dir = tempfile::tempdir().unwrap();
+
+        let result = download_file_with(&client_with_short_timeout(), &url, "a.txt", dir.path());
+
+        assert!(
+            matches!(result, Err(DownloadError::Timeout)),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn timeout_when_body_stalls() {
+        // Envia os cabeçalhos e parte do corpo, e então para de enviar dados.
+        let url = serve(|_, stream| {
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n0123456789");
+            let _ = stream.flush();
+            thread::sleep(std::time::Duration::from_secs(10));
+        });
+        let dir = tempfile::tempdir().unwrap();
+
+        let result = download_file_with(&client_with_short_timeout(), &url, "a.txt", dir.path());
+
+        assert!(
+            matches!(result, Err(DownloadError::Timeout)),
+            "{:?}",
+            result
+        );
+    }
+
+    /// SHA-256 de "abc" (vetor de teste do FIPS 180-2).
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn copy_with_progress_hashes_every_chunk() {
+        // Mais de um bloco de `BUFFER_SIZE`, para cobrir a atualização incremental do hash.
+        let content: Vec<u8> = (0..3 * BUFFER_SIZE + 17).map(|i| i as u8).collect();
+        let mut written = Vec::new();
+        let mut hasher = Sha256::new();
+
+        let copied =
+            copy_with_progress(&mut io::Cursor::new(&content), &mut written, |chunk, _| {
+                hasher.update(chunk)
+            })
+            .unwrap();
+
+        assert_eq!(copied, content.len() as u64);
+        assert_eq!(written, content);
+        assert_eq!(
+            to_hex(&hasher.finalize()),
+            to_hex(&Sha256::digest(&content))
+        );
+    }
+
+    #[test]
+    fn download_file_checked_accepts_matching_digest() {
+        let url = serve_content(b"abc");
+        let dir = tempfile::tempdir().unwrap();
+
+        // O digest esperado pode ser informado em maiúsculas.
+        let bytes =
+            download_file_checked(&url, "abc.txt", dir.path(), &ABC_SHA256.to_uppercase()).unwrap();
+
+        assert_eq!(bytes, 3);
+        assert_eq!(std::fs::read(dir.path().join("abc.txt")).unwrap(), b"abc");
+        assert!(!dir.path().join("abc.txt.part").exists());
+    }
+
+    #[test]
+    fn download_file_checked_removes_file_on_mismatch() {
+        let url = serve_content(b"abd");
+        let dir = tempfile::tempdir().unwrap();
+
+        let result = download_file_checked(&url, "abc.txt", dir.path(), ABC_SHA256);
+
+        match result {
+            Err(DownloadError::ChecksumMismatch { expected, actual }) => {
+                assert_eq!(expected, ABC_SHA256);
+                assert_eq!(actual, to_hex(&Sha256::digest(b"abd")));
+            }
+            other => panic!("resultado inesperado: {:?}", other),
+        }
+        assert!(!dir.path().join("abc.txt").exists());
+    }
+
+    #[test]
+    fn download_file_or_skip_skips_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"antigo").unwrap();
+
+        // O arquivo é pulado sem enviar requisição, então a URL não precisa existir.
+        let outcome = download_file_or_skip("http://127.0.0.1:1", "a.txt", dir.path(), false);
+
+        assert_eq!(outcome.unwrap(), DownloadOutcome::Skipped);
+        assert_eq!(std::fs::read(dir.path().join("a.txt")).unwrap(), b"antigo");
+    }
+
+    #[test]
+    fn download_file_or_skip_overwrites_when_asked() {
+        let url = serve_content(b"novo");
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"antigo").unwrap();
+
+        let outcome = download_file_or_skip(&url, "a.txt", dir.path(), true);
+
+        assert_eq!(outcome.unwrap(), DownloadOutcome::Downloaded(4));
+        assert_eq!(std::fs::read(dir.path().join("a.txt")).unwrap(), b"novo");
+    }
+
+    #[test]
+    fn download_file_or_skip_downloads_over_empty_leftover() {
+        let url = serve_content(b"novo");
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"").unwrap();
+
+        let outcome = download_file_or_skip(&url, "a.txt", dir.path(), false);
+
+        assert_eq!(outcome.unwrap(), DownloadOutcome::Downloaded(4));
+        assert_eq!(std::fs::read(dir.path().join("a.txt")).unwrap(), b"novo");
+    }
+
+    #[test]
+    fn resume_restarts_when_local_file_is_longer_than_remote() {
+        let url = serve_ranged(b"conteudo novo".to_vec());
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("dados.txt"), b"conteudo antigo, mais longo").unwrap();
+
+        let bytes = download_file_resumable(&url, "dados.txt", dir.path(), true).unwrap();
+
+        assert_eq!(bytes, 13);
+        assert_eq!(
+            std::fs::read(dir.path().join("dados.txt")).unwrap(),
+            b"conteudo novo"
+        );
+    }
+
+    #[test]
+    fn content_range_total_parses_known_sizes() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(content_range_total(&headers), None);
+
+        headers.insert(CONTENT_RANGE, "bytes */200".parse().unwrap());
+        assert_eq!(content_range_total(&headers), Some(200));
+
+        headers.insert(CONTENT_RANGE, "bytes 100-199/200".parse().unwrap());
+        assert_eq!(content_range_total(&headers), Some(200));
+
+        headers.insert(CONTENT_RANGE, "bytes 100-199/*".parse().unwrap());
+        assert_eq!(content_range_total(&headers), None);
+    }
+
+    #[test]
+    fn content_range_start_parses_the_first_byte() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(content_range_start(&headers), None);
+
+        headers.insert(CONTENT_RANGE, "bytes 100-199/200".parse().unwrap());
+        assert_eq!(content_range_start(&headers), Some(100));
+
+        headers.insert(CONTENT_RANGE, "bytes */200".parse().unwrap());
+        assert_eq!(content_range_start(&headers), None);
+    }
+
+    #[test]
+    fn local_progress_in_reports_size_of_regular_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("todos")).unwrap();
+        std::fs::write(dir.path().join("todos/1"), b"12345").unwrap();
+        std::fs::write(dir.path().join("vazio"), b"").unwrap();
+
+        assert_eq!(local_progress_in("todos/1", dir.path()), Some(5));
+        assert_eq!(local_progress_in("vazio", dir.path()), Some(0));
+        assert_eq!(local_progress_in("inexistente", dir.path()), None);
+        // Um diretório não é um download em andamento.
+        assert_eq!(local_progress_in("todos", dir.path()), None);
+    }
+
+    #[test]
+    fn local_progress_in_ignores_names_outside_dest_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("dest");
+        std::fs::create_dir(&dest).unwrap();
+        std::fs::write(dir.path().join("fora.txt"), b"segredo").unwrap();
+
+        assert_eq!(local_progress_in("../fora.txt", &dest), None);
+    }
+
+    #[test]
+    fn missing_reports_absent_empty_and_unsafe_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("dest");
+        std::fs::create_dir_all(dest.join("todos")).unwrap();
+        std::fs::write(dest.join("todos/1"), b"{}").unwrap();
+        std::fs::write(dest.join("vazio"), b"").unwrap();
+        // Existe, mas fora de `dest`: nunca conta como presente.
+        std::fs::write(dir.path().join("x"), b"fora").unwrap();
+
+        let result = missing(&["todos/1", "todos/2", "vazio", "../x", "todos"], &dest);
+
+        // "todos" é um diretório, não um arquivo baixado.
+        assert_eq!(result, ["todos/2", "vazio", "../x", "todos"]);
+    }
+
+    /// `Read` que contabiliza em `read_total` quantos bytes já entregou.
+    struct CountingReader<'a> {
+        inner: io::Cursor<Vec<u8>>,
+        read_total: &'a std::cell::Cell<u64>,
+    }
+
+    impl Read for CountingReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let bytes_read = self.inner.read(buf)?;
+            self.read_total
+                .set(self.read_total.get() + bytes_read as u64);
+            Ok(bytes_read)
+        }
+    }
+
+    /// `Write` lento, que registra o maior volume já lido e ainda não gravado no
+    /// momento de cada escrita.
+    struct SlowWriter<'a> {
+        read_total: &'a std::cell::Cell<u64>,
+        written: u64,
+        max_pending: u64,
+    }
+
+    impl Write for SlowWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            thread::sleep(std::time::Duration::from_millis(1));
+            let pending = self.read_total.get() - self.written;
+            self.max_pending = self.max_pending.max(pending);
+            self.written += buf.len() as u64;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn copy_with_progress_applies_backpressure_to_slow_writer() {
+        let content = vec![7u8; 20 * BUFFER_SIZE];
+        let read_total = std::cell::Cell::new(0);
+        let mut reader = CountingReader {
+            inner: io::Cursor::new(content),
+            read_total: &read_total,
+        };
+        let mut writer = SlowWriter {
+            read_total: &read_total,
+            written: 0,
+            max_pending: 0,
+        };
+
+        let copied = copy_with_progress(&mut reader, &mut writer, |_, _| {}).unwrap();
+
+        // Nunca se lê mais de um bloco à frente do que já foi gravado: a memória usada
+        // fica limitada ao buffer, por mais lento que seja o destino.
+        assert_eq!(copied, 20 * BUFFER_SIZE as u64);
+        assert_eq!(writer.written, copied);
+        assert!(
+            writer.max_pending <= BUFFER_SIZE as u64,
+            "{}",
+            writer.max_pending
+        );
+    }
+
+    #[test]
+    fn close_delimited_body_without_content_length_completes() {
+        // Resposta no estilo HTTP/1.0: sem `Content-Length`, o fim do corpo é o fechamento.
+        let url = serve(|_, stream| {
+            let _ = stream.write_all(b"HTTP/1.0 200 OK\r\n\r\ncorpo completo");
+        });
+        let dir = tempfile::tempdir().unwrap();
+
+        let bytes = download_file_to(&url, "a.txt", dir.path()).unwrap();
+
+        assert_eq!(bytes, 14);
+        assert_eq!(
+            std::fs::read(dir.path().join("a.txt")).unwrap(),
+            b"corpo completo"
+        );
+    }
+
+    #[test]
+    fn close_before_advertised_content_length_is_an_error() {
+        let url = serve(|_, stream| {
+            let _ = stream.write_all(b"HTTP/1.0 200 OK\r\nContent-Length: 100\r\n\r\nincompleto");
+        });
+        let dir = tempfile::tempdir().unwrap();
+
+        let result = download_file_to(&url, "a.txt", dir.path());
+
+        assert!(
+            matches!(result, Err(DownloadError::Copy(_))),
+            "{:?}",
+            result
+        );
+    }
+
+    /// `Write` que falha com `kind` nas primeiras `failures` escritas e depois grava normalmente.
+    struct FlakyWriter {
+        kind: io::ErrorKind,
+        failures: usize,
+        written: Vec<u8>,
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::Error::from(self.kind));
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn copy_with_progress_retries_interrupted_writes() {
+        let mut writer = FlakyWriter {
+            kind: io::ErrorKind::Interrupted,
+            failures: 3,
+            written: Vec::new(),
+        };
+
+        let copied =
+            copy_with_progress(&mut io::Cursor::new(b"dados"), &mut writer, |_, _| {}).unwrap();
+
+        assert_eq!(copied, 5);
+        assert_eq!(writer.written, b"dados");
+    }
+
+    #[test]
+    fn copy_with_progress_fails_on_permanent_write_errors() {
+        for kind in [io::ErrorKind::StorageFull, io::ErrorKind::PermissionDenied] {
+            let mut writer = FlakyWriter {
+                kind,
+                failures: 1,
+                written: Vec::new(),
+            };
+
+            let result = copy_with_progress(&mut io::Cursor::new(b"dados"), &mut writer, |_, _| {});
+
+            assert_eq!(result.unwrap_err().kind(), kind);
+            assert!(writer.written.is_empty());
+        }
+    }
+
+    /// Servidor que, após `delay`, responde com o caminho requisitado (ex: "a.txt") e
+    /// registra o maior número de requisições atendidas ao mesmo tempo.
+    fn serve_slow_echo(delay: std::time::Duration) -> (String, Arc<AtomicUsize>) {
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight_server = Arc::clone(&max_in_flight);
+
+        let url = serve(move |request, stream| {
+            let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight_server.fetch_max(current, Ordering::SeqCst);
+            thread::sleep(delay);
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+            respond(
+                stream,
+                "200 OK",
+                &[],
+                path.trim_start_matches('/').as_bytes(),
+            );
+        });
+
+        (url, max_in_flight)
+    }
+
+    #[test]
+    fn download_all_with_keeps_order_and_runs_concurrently() {
+        let delay = std::time::Duration::from_millis(200);
+        let (url, max_in_flight) = serve_slow_echo(delay);
+        let dir = tempfile::tempdir().unwrap();
+        let client = DownloadConfig::default().build_client().unwrap();
+        let filenames = ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt", "f.txt"];
+
+        let start = std::time::Instant::now();
+        let results = download_all_with(&client, &url, &filenames, dir.path(), 3);
+        let elapsed = start.elapsed();
+
+        // Em sequência levaria 6 × 200 ms; com 3 threads, duas rodadas (~400 ms).
+        assert!(elapsed < delay * 4, "{:?}", elapsed);
+        assert!(max_in_flight.load(Ordering::SeqCst) > 1);
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 3);
+
+        let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, filenames);
+        for (filename, result) in &results {
+            assert_eq!(*result.as_ref().unwrap(), filename.len() as u64);
+            assert_eq!(
+                std::fs::read(dir.path().join(filename)).unwrap(),
+                filename.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn download_all_with_treats_zero_concurrency_as_one() {
+        let (url, max_in_flight) = serve_slow_echo(std::time::Duration::from_millis(20));
+        let dir = tempfile::tempdir().unwrap();
+        let client = DownloadConfig::default().build_client().unwrap();
+
+        let results = download_all_with(&client, &url, &["a.txt", "b.txt", "c.txt"], dir.path(), 0);
+
+        assert!(
+            results.iter().all(|(_, result)| result.is_ok()),
+            "{:?}",
+            results
+        );
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn download_each_isolates_panics_per_file() {
+        let results = download_each(&["a", "b", "c"], 2, |filename| {
+            if filename == "b" {
+                panic!("falha simulada em {}", filename);
+            }
+            Ok(1)
+        });
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, "a");
+        assert!(matches!(results[0].1, Ok(1)));
+        assert!(
+            matches!(&results[1].1, Err(DownloadError::Panicked { message }) if message == "falha simulada em b"),
+            "{:?}",
+            results[1]
+        );
+        assert!(matches!(results[2].1, Ok(1)));
+    }
+
+    #[test]
+    fn download_file_with_options_combines_timeout_and_skip() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"antigo").unwrap();
+        let options = DownloadOptions {
+            skip_existing: true,
+            ..Default::default()
+        };
+
+        let outcome = download_file_with_options(
+            &client_with_short_timeout(),
+            "http://127.0.0.1:1",
+            "a.txt",
+            dir.path(),
+            &options,
+            |_, _| {},
+        );
+
+        assert_eq!(outcome.unwrap(), DownloadOutcome::Skipped);
+    }
+
+    #[test]
+    fn download_file_with_options_ignores_resume_when_checking_digest() {
+        let url = serve_ranged(b"abc".to_vec());
+        let dir = tempfile::tempdir().unwrap();
+        // Um prefixo local que não faz parte do conteúdo remoto.
+        std::fs::write(dir.path().join("abc.txt"), b"x").unwrap();
+        let options = DownloadOptions {
+            resume: true,
+            expected_sha256: Some(ABC_SHA256.to_string()),
+            ..Default::default()
+        };
+
+        let outcome = download_file_with_options(
+            &client_with_short_timeout(),
+            &url,
+            "abc.txt",
+            dir.path(),
+            &options,
+            |_, _| {},
+        );
+
+        assert_eq!(outcome.unwrap(), DownloadOutcome::Downloaded(3));
+        assert_eq!(std::fs::read(dir.path().join("abc.txt")).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn progress_reports_content_length_as_total() {
+        let content: &'static [u8] = &[1u8; 3 * BUFFER_SIZE + 5];
+        let url = serve_content(content);
+        let dir = tempfile::tempdir().unwrap();
+        let mut calls = Vec::new();
+
+        let bytes = download_file_with_progress(&url, "a.bin", dir.path(), |downloaded, total| {
+            calls.push((downloaded, total))
+        })
+        .unwrap();
+
+        let n = content.len() as u64;
+        assert_eq!(bytes, n);
+        assert_eq!(calls.last(), Some(&(n, Some(n))));
+        assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(calls.iter().all(|&(_, total)| total == Some(n)));
+    }
+
+    #[test]
+    fn progress_total_is_none_without_content_length() {
+        let url = serve(|_, stream| {
+            let _ = stream.write_all(b"HTTP/1.0 200 OK\r\n\r\nsem tamanho");
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let mut calls = Vec::new();
+
+        download_file_with_progress(&url, "a.txt", dir.path(), |downloaded, total| {
+            calls.push((downloaded, total))
+        })
+        .unwrap();
+
+        assert_eq!(calls.last(), Some(&(11, None)));
+    }
+
+    #[test]
+    fn progress_of_resumed_download_includes_existing_bytes() {
+        let url = serve_ranged(b"0123456789".to_vec());
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"0123").unwrap();
+        let options = DownloadOptions {
+            resume: true,
+            ..Default::default()
+        };
+        let mut calls = Vec::new();
+
+        let outcome = download_file_with_options(
+            &DownloadConfig::default().build_client().unwrap(),
+            &url,
+            "a.txt",
+            dir.path(),
+            &options,
+            |downloaded, total| calls.push((downloaded, total)),
+        );
+
+        assert_eq!(outcome.unwrap(), DownloadOutcome::Downloaded(6));
+        assert_eq!(calls, [(10, Some(10))]);
+    }
+
+    #[test]
+    fn download_file_checked_keeps_existing_file_on_mismatch() {
+        let url = serve_content(b"abd");
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("abc.txt"), b"abc").unwrap();
+
+        let result = download_file_checked(&url, "abc.txt", dir.path(), ABC_SHA256);
+
+        assert!(
+            matches!(result, Err(DownloadError::ChecksumMismatch { .. })),
+            "{:?}",
+            result
+        );
+        assert_eq!(std::fs::read(dir.path().join("abc.txt")).unwrap(), b"abc");
+        assert!(!dir.path().join("abc.txt.part").exists());
+    }
+
+    #[test]
+    fn download_file_checked_keeps_existing_file_on_copy_error() {
+        // Envia parte do corpo e fecha a conexão antes do `Content-Length` anunciado.
+        let url = serve(|_, stream| {
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nab");
+        });
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("abc.txt"), b"abc").unwrap();
+
+        let result = download_file_checked(&url, "abc.txt", dir.path(), ABC_SHA256);
+
+        assert!(
+            matches!(result, Err(DownloadError::Copy(_))),
+            "{:?}",
+            result
+        );
+        assert_eq!(std::fs::read(dir.path().join("abc.txt")).unwrap(), b"abc");
+        assert!(!dir.path().join("abc.txt.part").exists());
+    }
+
+    #[test]
+    fn download_file_checked_rejects_malformed_digest_before_request() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Nenhuma requisição é enviada, então a URL não precisa existir.
+        for digest in ["", "abc", &ABC_SHA256[1..], &ABC_SHA256.replace('a', "g")] {
+            let result = download_file_checked("http://127.0.0.1:1", "abc.txt", dir.path(), digest);
+
+            assert!(
+                matches!(&result, Err(DownloadError::InvalidChecksum { expected }) if expected == digest),
+                "{:?}",
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn group_by_host_groups_by_origin_and_omits_default_ports() {
+        let groups = group_by_host(&[
+            "https://example.com/files/a.jpg",
+            "https://example.com:443/files/b.jpg",
+            "http://example.com:8080/c.jpg",
+            "https://other.org/d.jpg",
+        ]);
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups["https://example.com"],
+            ["files/a.jpg", "files/b.jpg"]
+        );
+        assert_eq!(groups["http://example.com:8080"], ["c.jpg"]);
+        assert_eq!(groups["https://other.org"], ["d.jpg"]);
+    }
+
+    #[test]
+    fn group_by_host_keeps_percent_encoding_and_drops_fragment() {
+        let groups = group_by_host(&["https://example.com/files/a%20b.jpg#frag"]);
+
+        assert_eq!(groups["https://example.com"], ["files/a%20b.jpg"]);
+    }
+
+    #[test]
+    fn group_by_host_skips_urls_with_query() {
+        // Sem a query, as duas primeiras colapsariam no mesmo caminho "a.jpg".
+        let groups = group_by_host(&[
+            "https://example.com/a.jpg?v=1",
+            "https://example.com/a.jpg?v=2",
+            "https://example.com/b.jpg?token=abc",
+            "https://example.com/c.jpg",
+        ]);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups["https://example.com"], ["c.jpg"]);
+    }
+
+    #[test]
+    fn group_by_host_skips_invalid_and_hostless_urls() {
+        let groups = group_by_host(&["file:///tmp/x", "não é uma url", "https://example.com/a"]);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups["https://example.com"], ["a"]);
+    }
 }
-*/
\ No newline at end of file
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use reqwest::StatusCode;
+
+/// Erros que podem ocorrer durante o download de um arquivo.
+///
+/// Cada variante corresponde a uma etapa do download, permitindo que o chamador
+/// trate casos específicos (ex: arquivo inexistente) sem precisar analisar mensagens.
+#[derive(Debug)]
+pub enum DownloadError {
+    /// Falha ao enviar a requisição ou ao receber a resposta
+    /// (ex: erro de DNS, falha de conexão de rede).
+    Request(reqwest::Error),
+    /// O tempo limite configurado para a conexão ou para a requisição foi excedido.
+    Timeout,
+    /// O nome do arquivo faria com que ele fosse salvo fora do diretório de destino
+    /// (ex: contém `..` ou é um caminho absoluto).
+    UnsafePath { filename: String },
+    /// O servidor respondeu com 404 Not Found para o arquivo solicitado.
+    NotFound { filename: String },
+    /// O servidor respondeu com outro código de status de erro HTTP (4xx ou 5xx).
+    HttpStatus { status: StatusCode },
+    /// O caminho de destino já existe, mas não é um diretório.
+    NotADirectory { path: PathBuf },
+    /// Falha ao criar o diretório de destino.
+    CreateDir(io::Error),
+    /// Falha ao criar o arquivo local.
+    CreateFile(io::Error),
+    /// Falha ao copiar o conteúdo da resposta para o arquivo local.
+    Copy(io::Error),
+    /// O digest SHA-256 informado não tem 64 dígitos hexadecimais. Nenhuma requisição foi enviada.
+    InvalidChecksum { expected: String },
+    /// O digest SHA-256 do conteúdo baixado difere do esperado. O arquivo temporário já foi
+    /// removido, e um arquivo que já existia no destino foi mantido.
+    ChecksumMismatch { expected: String, actual: String },
+    /// A thread responsável pelo download entrou em pânico; `message` contém a
+    /// mensagem do pânico, quando disponível.
+    Panicked { message: String },
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Request(e) => write!(f, "Falha na requisição HTTP: {}", e),
+            DownloadError::Timeout => write!(f, "O tempo limite da requisição foi excedido."),
+            DownloadError::UnsafePath { filename } => write!(
+                f,
+                "O nome de arquivo '{}' não aponta para um arquivo dentro do diretório de destino.",
+                filename
+            ),
+            DownloadError::NotFound { filename } => write!(
+                f,
+                "Arquivo '{}' não encontrado. O servidor retornou 404 Not Found.",
+                filename
+            ),
+            DownloadError::HttpStatus { status } => {
+                write!(f, "O servidor retornou um erro HTTP. Status: {}", status)
+            }
+            DownloadError::NotADirectory { path } => write!(
+                f,
+                "O destino '{}' já existe, mas não é um diretório.",
+                path.display()
+            ),
+            DownloadError::CreateDir(e) => write!(f, "Falha ao criar o diretório: {}", e),
+            DownloadError::CreateFile(e) => write!(f, "Falha ao criar o arquivo local: {}", e),
+            DownloadError::Copy(e) => {
+                write!(
+                    f,
+                    "Falha ao copiar o conteúdo baixado para o arquivo: {}",
+                    e
+                )
+            }
+            DownloadError::InvalidChecksum { expected } => write!(
+                f,
+                "O checksum SHA-256 '{}' é inválido: são esperados 64 dígitos hexadecimais.",
+                expected
+            ),
+            DownloadError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "O checksum SHA-256 do arquivo baixado não confere. Esperado: {}, obtido: {}",
+                expected, actual
+            ),
+            DownloadError::Panicked { message } => {
+                write!(f, "A thread de download entrou em pânico: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DownloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DownloadError::Request(e) => Some(e),
+            DownloadError::CreateDir(e) | DownloadError::CreateFile(e) | DownloadError::Copy(e) => {
+                Some(e)
+            }
+            DownloadError::Timeout
+            | DownloadError::UnsafePath { .. }
+            | DownloadError::NotFound { .. }
+            | DownloadError::HttpStatus { .. }
+            | DownloadError::NotADirectory { .. }
+            | DownloadError::InvalidChecksum { .. }
+            | DownloadError::ChecksumMismatch { .. }
+            | DownloadError::Panicked { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for DownloadError {
+    /// Converte um erro do reqwest, separando os casos de tempo limite excedido
+    /// ([`DownloadError::Timeout`]) dos demais erros de requisição.
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            DownloadError::Timeout
+        } else {
+            DownloadError::Request(e)
+        }
+    }
+}
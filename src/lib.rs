@@ -0,0 +1,10 @@
+//! Funções para baixar arquivos via HTTP e salvá-los no diretório local "downloads".
+//!
+//! Com a feature `async`, o módulo `async` oferece versões assíncronas das
+//! funções de download, para uso dentro de um runtime tokio.
+
+#[cfg(feature = "async")]
+pub mod r#async;
+pub mod config;
+pub mod download;
+pub mod error;
@@ -1,10 +1,15 @@
+use std::process::ExitCode;
 use std::time::{Duration, Instant};
-mod download;
-use download::download_file;
+use test_thread_download::download::download_all;
 
 const URL: &str = "https://royalcast.com.br/files";
 
-fn main() {
+/// Exit code when some downloads succeeded and others failed.
+const EXIT_PARTIAL_FAILURE: u8 = 2;
+/// Exit code when every download failed.
+const EXIT_ALL_FAILED: u8 = 1;
+
+fn main() -> ExitCode {
     let filename_list = vec![
         "arquivo_1.jpg",
         "arquivo_2.jpg",
@@ -18,13 +23,30 @@ fn main() {
     ];
 
     let start = Instant::now();
-    for filename in filename_list {
-        download_file(URL, filename);
-    }
+    let results = download_all(URL, &filename_list, 4);
     let duration: Duration = start.elapsed();
+    let mut failed = 0;
+    for (filename, result) in &results {
+        if let Err(e) = result {
+            eprintln!("Failed to download '{}': {}", filename, e);
+            failed += 1;
+        }
+    }
+    let succeeded = results.len() - failed;
     println!(
-        "Downloaded files in {:.1} seconds",
-        duration.as_millis() as f32 / 1000.
+        "Downloaded files in {:.1} seconds: {} succeeded, {} failed",
+        duration.as_millis() as f32 / 1000.,
+        succeeded,
+        failed
     );
+
+    // 0 only if every download succeeded, so the binary can be chained with `&&`.
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else if succeeded == 0 {
+        ExitCode::from(EXIT_ALL_FAILED)
+    } else {
+        ExitCode::from(EXIT_PARTIAL_FAILURE)
+    }
 }
 